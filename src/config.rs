@@ -1,44 +1,183 @@
 use colored::*;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
 
-/// Get the path to the config file
+use crate::patternset::{Pattern, PatternSet};
+
+// ─── On-disk format ─────────────────────────────────────────────────────────────
+
+/// A named selection of categories plus any one-off extra patterns —
+/// `[profiles.docs]`, `[profiles.ci]`, activated with `--profile NAME`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub extra_ignores: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub no_color: bool,
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    #[serde(default)]
+    pub categories: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+}
+
+// ─── Paths ──────────────────────────────────────────────────────────────────────
+
+/// Get the path to the (TOML) config file, resolved through the
+/// platform-appropriate app-config directory (`XDG_CONFIG_HOME` on Unix,
+/// `%APPDATA%` on Windows, `~/Library/Application Support` on macOS).
+/// Falls back to the old hardcoded `~/.config/struct` only if the platform
+/// has no resolvable home directory at all.
 pub fn get_config_path() -> PathBuf {
+    match ProjectDirs::from("", "", "struct") {
+        Some(dirs) => dirs.config_dir().join("config.toml"),
+        None => fallback_config_path(),
+    }
+}
+
+fn fallback_config_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".config").join("struct").join("config.toml")
+}
+
+/// The pre-TOML config format, kept around only so `load_config` can
+/// migrate it the first time it finds no `config.toml`.
+fn get_legacy_config_path() -> PathBuf {
     let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
     PathBuf::from(home).join(".config").join("struct").join("ignores.txt")
 }
 
-/// Load patterns from config file
-pub fn load_config_patterns() -> Vec<String> {
+// ─── Load / save ────────────────────────────────────────────────────────────────
+
+/// Load the TOML config, migrating a legacy `ignores.txt` into it the first
+/// time `config.toml` doesn't exist yet. A corrupt `config.toml` falls back
+/// to an empty config rather than crashing the whole program.
+fn load_config() -> Config {
     let config_path = get_config_path();
     if let Ok(content) = fs::read_to_string(&config_path) {
-        content.lines()
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty() && !s.starts_with('#'))
-            .collect()
-    } else {
-        Vec::new()
+        return toml::from_str(&content).unwrap_or_default();
+    }
+    migrate_legacy_config().unwrap_or_default()
+}
+
+fn migrate_legacy_config() -> Option<Config> {
+    let content = fs::read_to_string(get_legacy_config_path()).ok()?;
+    let ignore: Vec<String> = content
+        .lines()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty() && !s.starts_with('#'))
+        .collect();
+    if ignore.is_empty() {
+        return None;
+    }
+
+    let config = Config { ignore, ..Config::default() };
+    if save_config(&config).is_ok() {
+        eprintln!(
+            "{}",
+            format!(
+                "migrated {} into {}",
+                get_legacy_config_path().display(),
+                get_config_path().display()
+            )
+            .bright_black()
+        );
     }
+    Some(config)
 }
 
-/// Save patterns to config file
-fn save_config_patterns(patterns: &[String]) -> std::io::Result<()> {
+fn save_config(config: &Config) -> std::io::Result<()> {
     let config_path = get_config_path();
     if let Some(parent) = config_path.parent() {
         fs::create_dir_all(parent)?;
     }
-    fs::write(&config_path, patterns.join("\n"))
+    let content = toml::to_string_pretty(config)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    fs::write(&config_path, content)
+}
+
+/// A documented starting point, written verbatim rather than serialized —
+/// serializing `Config::default()` would produce an empty, comment-free
+/// file, which defeats the point of `struct config init`.
+const DEFAULT_CONFIG_TOML: &str = r#"# struct config — see `struct --help` for how these are used.
+#
+# `ignore` is the flat list that `struct add`/`struct remove`/`struct list`
+# manage directly, and that every search/tree walk picks up automatically.
+ignore = [
+    "node_modules",
+    ".git",
+    "target",
+    "__pycache__",
+]
+
+# Group related patterns under a name, then pull a whole group into a
+# `--profile` via the `categories` list below.
+# [categories]
+# python = ["__pycache__", "*.pyc", ".venv"]
+
+# Profiles bundle categories (and any one-off extras) behind a single
+# `--profile NAME` flag, e.g. `struct search "*" . --profile ci`.
+# [profiles.ci]
+# categories = ["python"]
+# extra_ignores = ["*.log"]
+"#;
+
+// ─── Init ───────────────────────────────────────────────────────────────────────
+
+/// `struct config init` — write a commented default config.toml, mirroring
+/// rustfmt's `--dump-default-config`. Refuses to clobber an existing file.
+pub fn init_config() {
+    let config_path = get_config_path();
+    if config_path.exists() {
+        println!(
+            "{} {}",
+            "already exists:".yellow(),
+            config_path.display().to_string().cyan()
+        );
+        return;
+    }
+
+    if let Some(parent) = config_path.parent() {
+        if let Err(e) = fs::create_dir_all(parent) {
+            eprintln!("failed to create config directory: {}", e);
+            return;
+        }
+    }
+
+    if let Err(e) = fs::write(&config_path, DEFAULT_CONFIG_TOML) {
+        eprintln!("failed to write config: {}", e);
+        return;
+    }
+
+    println!(
+        "{} {}",
+        "wrote default config to".green(),
+        config_path.display().to_string().cyan()
+    );
 }
 
+// ─── Global ignore list (add/remove/list/clear) ──────────────────────────────────
+
 /// Add a pattern to the config file
 pub fn add_config_pattern(pattern: String) {
-    let mut patterns = load_config_patterns();
-    if patterns.contains(&pattern) {
+    let mut config = load_config();
+    if config.ignore.contains(&pattern) {
         println!("{} already in config", pattern.yellow());
         return;
     }
-    patterns.push(pattern.clone());
-    if let Err(e) = save_config_patterns(&patterns) {
+    config.ignore.push(pattern.clone());
+    if let Err(e) = save_config(&config) {
         eprintln!("failed to save config: {}", e);
         return;
     }
@@ -48,48 +187,300 @@ pub fn add_config_pattern(pattern: String) {
 
 /// Remove a pattern from the config file
 pub fn remove_config_pattern(pattern: String) {
-    let mut patterns = load_config_patterns();
-    let before_len = patterns.len();
-    patterns.retain(|p| p != &pattern);
-    
-    if patterns.len() == before_len {
+    let mut config = load_config();
+    let before_len = config.ignore.len();
+    config.ignore.retain(|p| p != &pattern);
+
+    if config.ignore.len() == before_len {
         println!("{} not found in config", pattern.yellow());
         return;
     }
-    
-    if let Err(e) = save_config_patterns(&patterns) {
+
+    if let Err(e) = save_config(&config) {
         eprintln!("failed to save config: {}", e);
         return;
     }
     println!("{} removed from config", pattern.red());
 }
 
+/// A disabled pattern is stored as `#!pattern` so it round-trips through the
+/// `#`-comment filter in [`crate::patternset::Pattern::compile`] — disabled
+/// entries are simply never compiled, without needing a parallel field.
+fn is_disabled(entry: &str) -> bool {
+    entry.starts_with("#!")
+}
+
+fn strip_disabled(entry: &str) -> &str {
+    entry.strip_prefix("#!").unwrap_or(entry)
+}
+
+/// Rename a pattern in place, preserving both its position in the list and
+/// its enabled/disabled state.
+pub fn edit_config_pattern(old: String, new: String) {
+    let mut config = load_config();
+    let Some(slot) = config.ignore.iter_mut().find(|p| strip_disabled(p) == old) else {
+        println!("{} not found in config", old.yellow());
+        return;
+    };
+    *slot = if is_disabled(slot) { format!("#!{}", new) } else { new.clone() };
+
+    if let Err(e) = save_config(&config) {
+        eprintln!("failed to save config: {}", e);
+        return;
+    }
+    println!("{} renamed to {}", old.red(), new.green());
+}
+
+/// Disable a pattern without removing it, so it can be re-enabled later.
+pub fn disable_config_pattern(pattern: String) {
+    let mut config = load_config();
+    let Some(slot) = config.ignore.iter_mut().find(|p| strip_disabled(p) == pattern) else {
+        println!("{} not found in config", pattern.yellow());
+        return;
+    };
+    if is_disabled(slot) {
+        println!("{} already disabled", pattern.yellow());
+        return;
+    }
+    *slot = format!("#!{}", pattern);
+
+    if let Err(e) = save_config(&config) {
+        eprintln!("failed to save config: {}", e);
+        return;
+    }
+    println!("{} disabled", pattern.yellow());
+}
+
+/// Re-enable a previously disabled pattern.
+pub fn enable_config_pattern(pattern: String) {
+    let mut config = load_config();
+    let Some(slot) = config.ignore.iter_mut().find(|p| strip_disabled(p) == pattern) else {
+        println!("{} not found in config", pattern.yellow());
+        return;
+    };
+    if !is_disabled(slot) {
+        println!("{} already enabled", pattern.yellow());
+        return;
+    }
+    *slot = pattern.clone();
+
+    if let Err(e) = save_config(&config) {
+        eprintln!("failed to save config: {}", e);
+        return;
+    }
+    println!("{} enabled", pattern.green());
+}
+
 /// List all patterns in the config file
 pub fn list_config_patterns() {
-    let patterns = load_config_patterns();
-    if patterns.is_empty() {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let resolved = resolve_patterns(&cwd, None);
+    if resolved.is_empty() {
         println!("no custom patterns configured");
         println!("add some with: struct add \"pattern\"");
-        return;
+    } else {
+        println!("{}", "custom ignore patterns:".bright_black());
+        for (source, pattern) in resolved {
+            if let Some(bare) = pattern.strip_prefix("#!") {
+                println!(
+                    "  {}  {}  {}",
+                    bare.bright_black(),
+                    "(disabled)".yellow(),
+                    format!("[{}]", source.label()).bright_black()
+                );
+            } else {
+                println!("  {}  {}", pattern.cyan(), format!("[{}]", source.label()).bright_black());
+            }
+        }
     }
-    
-    println!("{}", "custom ignore patterns:".bright_black());
-    for pattern in patterns {
-        println!("  {}", pattern.cyan());
+
+    let config = load_config();
+    if !config.profiles.is_empty() {
+        let mut names: Vec<&String> = config.profiles.keys().collect();
+        names.sort();
+        println!("\n{}", "profiles:".bright_black());
+        for name in names {
+            println!("  {}", name.cyan());
+        }
     }
+
     println!("\nconfig file: {}", get_config_path().display().to_string().bright_black());
 }
 
-/// Clear all patterns from the config file
+/// Clear all patterns from the config file (categories and profiles are left
+/// alone — only the flat global ignore list is emptied).
 pub fn clear_config_patterns() {
-    let config_path = get_config_path();
-    if config_path.exists() {
-        if let Err(e) = fs::remove_file(&config_path) {
-            eprintln!("failed to clear config: {}", e);
-            return;
-        }
-        println!("{}", "cleared all custom patterns".green());
-    } else {
+    let mut config = load_config();
+    if config.ignore.is_empty() {
         println!("no config file to clear");
+        return;
+    }
+    config.ignore.clear();
+    if let Err(e) = save_config(&config) {
+        eprintln!("failed to clear config: {}", e);
+        return;
+    }
+    println!("{}", "cleared all custom patterns".green());
+}
+
+// ─── Profiles ───────────────────────────────────────────────────────────────────
+
+/// Resolve a `--profile NAME` selection into the extra patterns it
+/// contributes: each named category's patterns, plus the profile's own
+/// `extra_ignores`. Returns an empty list (with a warning) for an unknown
+/// profile name rather than failing the whole command.
+fn profile_patterns(config: &Config, profile: &str) -> Vec<String> {
+    let Some(p) = config.profiles.get(profile) else {
+        eprintln!("{}", format!("warning: no profile named '{}'", profile).yellow());
+        return Vec::new();
+    };
+
+    let mut patterns = Vec::new();
+    for category in &p.categories {
+        match config.categories.get(category) {
+            Some(entries) => patterns.extend(entries.iter().cloned()),
+            None => eprintln!("{}", format!("warning: profile '{}' references unknown category '{}'", profile, category).yellow()),
+        }
     }
-}
\ No newline at end of file
+    patterns.extend(p.extra_ignores.iter().cloned());
+    patterns
+}
+
+// ─── Layered resolution (global + project-local + profile) ──────────────────────
+
+/// Where a resolved pattern came from — used so `list_config_patterns` can
+/// show provenance instead of a flat, unattributed list.
+#[derive(Debug, Clone)]
+pub enum ConfigSource {
+    /// The global `config.toml`'s `ignore` list.
+    Global,
+    /// A `.structignore` found while climbing from the search/tree root up
+    /// to the filesystem root, tagged with the directory it lives in.
+    Project(PathBuf),
+    /// A category/extra_ignores pattern pulled in by `--profile NAME`.
+    Profile(String),
+}
+
+impl ConfigSource {
+    pub fn label(&self) -> String {
+        match self {
+            ConfigSource::Global => "global".to_string(),
+            ConfigSource::Project(dir) => format!("project: {}", dir.display()),
+            ConfigSource::Profile(name) => format!("profile: {}", name),
+        }
+    }
+}
+
+/// Walk parent directories of `start`, collecting any `.structignore` files
+/// along the way. Returned outermost-first (closest to the filesystem root)
+/// down to the directory nearest `start`, so merging them in order leaves
+/// the nearest file's patterns last — and therefore taking precedence.
+fn collect_project_patterns(start: &Path) -> Vec<(ConfigSource, String)> {
+    let mut found = Vec::new();
+    let mut dir = match start.canonicalize() {
+        Ok(d) => d,
+        Err(_) => return found,
+    };
+
+    loop {
+        if let Ok(content) = fs::read_to_string(dir.join(".structignore")) {
+            let lines: Vec<(ConfigSource, String)> = content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| (ConfigSource::Project(dir.clone()), line.to_string()))
+                .collect();
+            // Outer directories are reached later, so each file's block goes
+            // in front; the lines within a file keep their own order.
+            found.splice(0..0, lines);
+        }
+        match dir.parent() {
+            Some(parent) => dir = parent.to_path_buf(),
+            None => break,
+        }
+    }
+
+    found
+}
+
+/// Layered pattern resolution, inspired by jj's Default/User/Repo/CommandArg
+/// source stack: the global config is the base layer, `.structignore` files
+/// discovered by walking up from `start` are merged over it (nearer wins),
+/// and an active `--profile` contributes its categories/extras on top of
+/// that. Patterns from `--ignore`/`-i` flags are layered on top of all of
+/// this by the caller, since those are the most specific source of all.
+pub fn resolve_patterns(start: &Path, profile: Option<&str>) -> Vec<(ConfigSource, String)> {
+    let config = load_config();
+
+    let mut resolved: Vec<(ConfigSource, String)> =
+        config.ignore.iter().cloned().map(|p| (ConfigSource::Global, p)).collect();
+    resolved.extend(collect_project_patterns(start));
+
+    if let Some(name) = profile {
+        resolved.extend(
+            profile_patterns(&config, name)
+                .into_iter()
+                .map(|p| (ConfigSource::Profile(name.to_string()), p)),
+        );
+    }
+
+    resolved
+}
+
+/// Compile the resolved global + project-local + profile patterns for
+/// `start` into a single gitignore-semantics `PatternSet` — order-sensitive,
+/// with `!` negation and directory-only `/` suffixes honored, the same as
+/// `.gitignore`/`.ignore` files get via [`crate::gitignore::IgnoreStack`].
+pub fn compiled_patterns(start: &Path, profile: Option<&str>) -> PatternSet {
+    let lines: Vec<String> =
+        resolve_patterns(start, profile).into_iter().map(|(_, pattern)| pattern).collect();
+    PatternSet::compile(lines)
+}
+
+// ─── Dry-run ────────────────────────────────────────────────────────────────────
+
+/// `struct config test [PATH]` — walk `path` and, for each resolved pattern,
+/// print which files/dirs underneath it the pattern would actually match.
+/// Lets a new rule be validated before it's relied on to silently drop
+/// things from a real search or tree walk. Disabled (`#!`) patterns are
+/// skipped, same as they are everywhere else.
+pub fn test_config_patterns(path: &Path, profile: Option<&str>) {
+    let resolved = resolve_patterns(path, profile);
+    if resolved.is_empty() {
+        println!("no patterns configured");
+        return;
+    }
+
+    for (source, raw) in &resolved {
+        let Some(pattern) = Pattern::compile(raw) else {
+            continue;
+        };
+
+        let mut matches: Vec<(String, bool)> = Vec::new();
+        for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
+            let rel = entry.path().strip_prefix(path).unwrap_or(entry.path());
+            if rel.as_os_str().is_empty() {
+                continue;
+            }
+            let rel_str = rel.to_string_lossy().replace('\\', "/");
+            let is_dir = entry.file_type().is_dir();
+            if pattern.is_match(&rel_str, is_dir) {
+                matches.push((rel_str, is_dir));
+            }
+        }
+
+        println!("{}  {}", raw.cyan(), format!("[{}]", source.label()).bright_black());
+        if matches.is_empty() {
+            println!("  {}", "(no matches)".bright_black());
+        } else {
+            for (rel_str, is_dir) in matches {
+                if is_dir {
+                    println!("  {}", format!("{}/", rel_str).blue());
+                } else {
+                    println!("  {}", rel_str.bright_black());
+                }
+            }
+        }
+        println!();
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use crate::patternset::PatternSet;
+
+/// Patterns from a single `.gitignore`/`.ignore` file, scoped to the
+/// directory it lives in.
+struct IgnoreFile {
+    dir: PathBuf,
+    patterns: PatternSet,
+}
+
+impl IgnoreFile {
+    fn load(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        Some(IgnoreFile {
+            dir: path.parent()?.to_path_buf(),
+            patterns: PatternSet::compile(content.lines()),
+        })
+    }
+
+    fn evaluate(&self, canonical_path: &Path, is_dir: bool) -> Option<bool> {
+        let rel = canonical_path.strip_prefix(&self.dir).ok()?;
+        let rel_str = rel.to_string_lossy().replace('\\', "/");
+        self.patterns.evaluate(&rel_str, is_dir)
+    }
+}
+
+/// The merged set of `.gitignore`/`.ignore` files that apply to a walk.
+///
+/// Two sources feed it: the ancestor chain climbed once at `load` time
+/// (covers `.gitignore`s *above* the search root), and nested files
+/// discovered lazily as the walk descends *below* it — both keyed off
+/// `base`, the canonicalized search root, since paths flowing in from the
+/// walk aren't guaranteed to already be canonical (e.g. the default `.`).
+pub struct IgnoreStack {
+    base: Option<PathBuf>,
+    ancestors: Vec<IgnoreFile>,
+    nested: RwLock<HashMap<PathBuf, Vec<Arc<IgnoreFile>>>>,
+}
+
+impl IgnoreStack {
+    /// Empty stack — used when `-n all`/`-n gitignore` disables this subsystem.
+    pub fn empty() -> Self {
+        IgnoreStack { base: None, ancestors: Vec::new(), nested: RwLock::new(HashMap::new()) }
+    }
+
+    /// Climb from `start_dir` upward, collecting `.gitignore` and `.ignore`
+    /// files along the way, stopping once a directory containing `.git` has
+    /// been processed.
+    pub fn load(start_dir: &Path) -> Self {
+        let mut ancestors = Vec::new();
+        let base = match start_dir.canonicalize() {
+            Ok(d) => d,
+            Err(_) => return IgnoreStack { base: None, ancestors, nested: RwLock::new(HashMap::new()) },
+        };
+
+        let mut dir = base.clone();
+        loop {
+            for name in [".gitignore", ".ignore"] {
+                if let Some(f) = IgnoreFile::load(&dir.join(name)) {
+                    ancestors.push(f);
+                }
+            }
+
+            let is_repo_root = dir.join(".git").exists();
+            match dir.parent() {
+                Some(parent) if !is_repo_root => dir = parent.to_path_buf(),
+                _ => break,
+            }
+        }
+
+        // Closest to the walked path should be evaluated last so it wins —
+        // we loaded nearest-first, so reverse to root-first order.
+        ancestors.reverse();
+        IgnoreStack { base: Some(base), ancestors, nested: RwLock::new(HashMap::new()) }
+    }
+
+    /// Is `path` ignored? `path` is canonicalized first — the walk builds
+    /// paths by joining onto whatever start path the caller gave it (often
+    /// the uncanonicalized `.`), and every loaded `IgnoreFile`'s `dir` is
+    /// canonical, so comparing raw paths against it would never match.
+    ///
+    /// Evaluated across the ancestor chain plus every nested `.gitignore`
+    /// between the search root and `path`, root-first — a file only
+    /// overrides the verdict when one of its own patterns actually matches,
+    /// so a `.gitignore` closer to the file takes precedence over one
+    /// further up without a silent "no match" resetting an outer exclusion.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Some(base) = self.base.as_ref() else {
+            return false;
+        };
+        let canon = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+        let mut ignored = false;
+        for file in &self.ancestors {
+            if let Some(verdict) = file.evaluate(&canon, is_dir) {
+                ignored = verdict;
+            }
+        }
+        for file in self.nested_files(&canon, base) {
+            if let Some(verdict) = file.evaluate(&canon, is_dir) {
+                ignored = verdict;
+            }
+        }
+        ignored
+    }
+
+    /// `.gitignore`/`.ignore` files in directories below `base`, down to and
+    /// including `path`'s own parent — the upward climb in `load` only ever sees
+    /// `base` and its ancestors, so a `.gitignore` dropped into a
+    /// subdirectory as the walk descends is otherwise never picked up.
+    fn nested_files(&self, path: &Path, base: &Path) -> Vec<Arc<IgnoreFile>> {
+        let Ok(rel) = path.strip_prefix(base) else {
+            return Vec::new();
+        };
+
+        let mut components: Vec<_> = rel.components().collect();
+        components.pop(); // the entry's own name — its own dir is handled by the caller, not here
+
+        let mut out = Vec::new();
+        let mut dir = base.to_path_buf();
+        for component in components {
+            dir.push(component);
+            out.extend(self.cached_files(&dir));
+        }
+        out
+    }
+
+    /// Look up (and lazily populate) the `.gitignore`/`.ignore` files for
+    /// one directory. `is_ignored` is called concurrently from the parallel
+    /// walk's worker threads, so the cache is behind an `RwLock` rather than
+    /// plain interior mutability.
+    fn cached_files(&self, dir: &Path) -> Vec<Arc<IgnoreFile>> {
+        if let Some(hit) = self.nested.read().unwrap().get(dir) {
+            return hit.clone();
+        }
+
+        let loaded: Vec<Arc<IgnoreFile>> = [".gitignore", ".ignore"]
+            .into_iter()
+            .filter_map(|name| IgnoreFile::load(&dir.join(name)))
+            .map(Arc::new)
+            .collect();
+
+        self.nested.write().unwrap().insert(dir.to_path_buf(), loaded.clone());
+        loaded
+    }
+}
+
+/// Flatten every `.gitignore`/`.ignore` pattern reachable by climbing from
+/// `start_dir` upward into plain ignore strings, for callers that only have
+/// a flat, OR-matched ignore list to plug into (the plain tree view) rather
+/// than a directory-scoped [`IgnoreStack`]. Negated (`!`) lines are dropped
+/// since a flat OR-list can't express "un-ignore"; full fidelity (negation,
+/// per-directory precedence, nested `.gitignore`s below the root) needs the
+/// same `IgnoreStack`-aware integration `search` has.
+pub fn flat_patterns(start_dir: &Path) -> Vec<String> {
+    let mut dir = match start_dir.canonicalize() {
+        Ok(d) => d,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut patterns = Vec::new();
+    loop {
+        for name in [".gitignore", ".ignore"] {
+            if let Ok(content) = fs::read_to_string(dir.join(name)) {
+                for line in content.lines() {
+                    let line = line.trim();
+                    if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+                        continue;
+                    }
+                    patterns.push(line.trim_end_matches('/').to_string());
+                }
+            }
+        }
+
+        let is_repo_root = dir.join(".git").exists();
+        match dir.parent() {
+            Some(parent) if !is_repo_root => dir = parent.to_path_buf(),
+            _ => break,
+        }
+    }
+
+    patterns
+}
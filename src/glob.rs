@@ -0,0 +1,72 @@
+use globset::{GlobBuilder, GlobMatcher};
+use std::path::Path;
+
+/// A compiled pattern for matching file names or relative paths.
+///
+/// Plain text with no glob metacharacters falls back to a case-insensitive
+/// substring match, matching this crate's existing behavior. Anything else
+/// is compiled by `globset`, which gives us correct `**` recursion, `?`,
+/// `[...]`/`[!...]` character classes, and `{a,b}` alternation for free.
+pub enum GlobPattern {
+    Glob { matcher: GlobMatcher, has_sep: bool },
+    Substring(String),
+}
+
+/// Characters that signal the pattern should go through the glob compiler
+/// rather than the plain-substring fallback.
+fn has_glob_meta(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '[', ']', '{', '}'])
+}
+
+impl GlobPattern {
+    pub fn build(pattern: &str) -> Result<Self, String> {
+        if pattern.is_empty() {
+            return Err("pattern cannot be empty — use \"*\" to match everything".to_string());
+        }
+
+        if has_glob_meta(pattern) {
+            // `literal_separator` keeps a lone `*` from crossing `/` (so
+            // `build/*.o` doesn't also match `build/sub/x.o`) while leaving
+            // `**` free to span directories, matching gitignore/fd semantics.
+            let glob = GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| e.to_string())?;
+            Ok(GlobPattern::Glob {
+                matcher: glob.compile_matcher(),
+                has_sep: pattern.contains('/'),
+            })
+        } else {
+            Ok(GlobPattern::Substring(pattern.to_lowercase()))
+        }
+    }
+
+    /// Match against a bare file name (used when the pattern has no `/`).
+    pub fn is_match_name(&self, name: &str) -> bool {
+        match self {
+            GlobPattern::Glob { matcher, has_sep } => {
+                if *has_sep {
+                    false
+                } else {
+                    matcher.is_match(name)
+                }
+            }
+            GlobPattern::Substring(needle) => name.to_lowercase().contains(needle.as_str()),
+        }
+    }
+
+    /// Match against a path relative to the search root — only meaningful
+    /// for patterns that contain a `/`, e.g. `src/**/*.rs`.
+    pub fn is_match_path(&self, rel_path: &Path) -> bool {
+        match self {
+            GlobPattern::Glob { matcher, has_sep } => *has_sep && matcher.is_match(rel_path),
+            GlobPattern::Substring(_) => false,
+        }
+    }
+
+    /// True if this pattern should be evaluated against the full relative
+    /// path rather than just the file name.
+    pub fn is_path_pattern(&self) -> bool {
+        matches!(self, GlobPattern::Glob { has_sep: true, .. })
+    }
+}
@@ -1,4 +1,4 @@
-use regex::Regex;
+use crate::glob::GlobPattern;
 
 /// Check if a directory should be ignored by default
 pub fn should_ignore_dir(name: &str) -> bool {
@@ -27,6 +27,6 @@ pub fn should_ignore_file(name: &str) -> bool {
 }
 
 /// Check if a name matches any of the custom patterns
-pub fn matches_custom_pattern(name: &str, patterns: &[Regex]) -> bool {
-    patterns.iter().any(|re| re.is_match(name))
+pub fn matches_custom_pattern(name: &str, patterns: &[GlobPattern]) -> bool {
+    patterns.iter().any(|p| p.is_match_name(name))
 }
\ No newline at end of file
@@ -1,21 +1,32 @@
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser, ValueHint};
+use clap_complete::Shell;
 use colored::*;
 use git2::Repository;
-use regex::Regex;
 use std::ffi::OsString;
 use std::path::PathBuf;
 
 mod config;
 mod display;
+mod gitignore;
+mod glob;
 mod ignores;
+mod parallel_walk;
+mod patternset;
 mod search;
+mod size;
 mod summary;
 mod utils;
 
+use crate::gitignore::IgnoreStack;
+use crate::glob::GlobPattern;
+use crate::size::SizeConstraint;
+
 use crate::config::{
-    add_config_pattern, clear_config_patterns, list_config_patterns, load_config_patterns,
-    remove_config_pattern,
+    add_config_pattern, clear_config_patterns, compiled_patterns, disable_config_pattern,
+    edit_config_pattern, enable_config_pattern, init_config, list_config_patterns,
+    remove_config_pattern, resolve_patterns, test_config_patterns,
 };
+use crate::patternset::PatternSet;
 use display::{
     display_tree, get_git_changed_files, get_git_staged_files, get_git_tracked_files,
     get_git_untracked_files, GitMode, StructConfig,
@@ -50,16 +61,31 @@ SEARCH:
   struct search \"gui*\" . -f            flat output (full paths)
   struct search \"*.log\" . -i \"venv\"    search, ignoring venv
   struct search \"*.wav\" . -i \"win,Linux\"
+  struct search \"*.log\" . -S +50M     only files at least 50MB
+  struct search \"*\" . -S +1M -S -100M only files between 1MB and 100MB
 
 CONFIG:
   struct add \"pattern\"                 add to persistent ignores
   struct remove \"pattern\"              remove from persistent ignores
-  struct list                          list config patterns
+  struct edit \"old\" \"new\"              rename a pattern in place
+  struct disable \"pattern\"             turn a pattern off without removing it
+  struct enable \"pattern\"              turn a disabled pattern back on
+  struct list                          list config patterns (with source)
   struct clear                         clear all config patterns
+  .structignore                        drop one in any parent dir for
+                                        project-local patterns (nearest wins)
+  struct search \"*\" . --profile ci     pull in a named profile's categories
+                                        (config.toml: [categories], [profiles])
+  struct config init                   write a commented default config.toml
+  struct config test [path]            show what each resolved pattern matches
+
+SHELL COMPLETIONS:
+  struct completions zsh > _struct     generate a completion script
+                                        (bash, zsh, fish, powershell, elvish)
 
 FLAGS:
   -i \"p1,p2\"   ignore patterns (dirs or files, comma-separated)
-  -n TARGET    un-ignore: a pattern name, 'defaults', 'config', or 'all'
+  -n TARGET    un-ignore: a pattern name, 'defaults', 'config', 'gitignore', or 'all'
                (can be specified multiple times: -n defaults -n config)
   -z           show file/dir sizes
   -s SIZE      skip dirs larger than SIZE megabytes
@@ -115,6 +141,9 @@ struct Flags {
     #[arg(short = 'n', long = "no-ignore", value_name = "TARGET", action = clap::ArgAction::Append, hide = true)]
     no_ignore: Vec<String>,
 
+    #[arg(long = "profile", value_name = "NAME", hide = true)]
+    profile: Option<String>,
+
     #[arg(short = 'h', long = "help", action = clap::ArgAction::SetTrue, hide = true)]
     help: bool,
 }
@@ -125,31 +154,102 @@ enum Commands {
     Add { pattern: String },
     /// Remove a pattern from the persistent ignore config
     Remove { pattern: String },
+    /// Rename a pattern in place, preserving its position in the list
+    Edit { old: String, new: String },
+    /// Disable a pattern without removing it
+    Disable { pattern: String },
+    /// Re-enable a previously disabled pattern
+    Enable { pattern: String },
     /// List all persistent ignore patterns
     List,
     /// Clear all persistent ignore patterns
     Clear,
+    /// Manage the config file itself (separate from the pattern shortcuts above)
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
     /// Search for files/dirs matching a pattern
     ///
     /// Plain text = substring match. Wildcards (* ?) = glob match.
     Search {
+        #[arg(index = 1)]
         pattern: String,
-        #[arg(default_value = ".")]
+        // Pinned with explicit `index`s rather than left to declaration
+        // order: `build_cli`'s `mut_arg` calls re-insert these args, which
+        // would otherwise silently shuffle them to the end of the
+        // positional order.
+        #[arg(index = 2, default_value = ".")]
         path: PathBuf,
-        #[arg(value_name = "DEPTH", default_value = "0")]
+        #[arg(index = 3, value_name = "DEPTH", default_value = "0")]
         depth: usize,
         #[arg(short = 'f', long = "flat")]
         flat: bool,
         #[arg(short = 'i', long = "ignore", value_name = "PATTERNS")]
         ignore_patterns: Option<String>,
+        /// fd-style size constraint, e.g. +10M, -500k, 1G — can repeat to AND
+        #[arg(
+            short = 'S',
+            long = "size",
+            value_name = "SIZE",
+            action = clap::ArgAction::Append,
+            allow_hyphen_values = true
+        )]
+        size: Vec<String>,
+        #[arg(long = "profile", value_name = "NAME")]
+        profile: Option<String>,
+    },
+    /// Print a shell completion script to stdout
+    ///
+    /// e.g. `struct completions zsh > _struct`
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum ConfigCommand {
+    /// Write a commented default config.toml (refuses to overwrite an existing one)
+    Init,
+    /// Show which files/dirs under PATH each resolved pattern would match
+    Test {
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        #[arg(long = "profile", value_name = "NAME")]
+        profile: Option<String>,
     },
 }
 
+// ─── Shared clap command ──────────────────────────────────────────────────────
+
+/// Build the clap `Command` once, so flag parsing and the `completions`
+/// generator stay in sync. Most top-level flags are `hide = true` so they
+/// don't clutter our own hand-written `HELP` text, but shell completions
+/// should still offer them — so unhide everything here and layer on the
+/// value hints that make completions actually useful (directories for paths,
+/// free text for pattern-ish flags).
+fn build_cli() -> clap::Command {
+    Flags::command()
+        .mut_args(|arg| arg.hide(false))
+        .mut_arg("ignore_patterns", |a| a.value_hint(ValueHint::Other))
+        .mut_arg("max_size_mb", |a| a.value_hint(ValueHint::Other))
+        .mut_arg("no_ignore", |a| a.value_hint(ValueHint::Other))
+        .mut_subcommand("search", |c| {
+            c.mut_arg("path", |a| a.value_hint(ValueHint::DirPath))
+                .mut_arg("ignore_patterns", |a| a.value_hint(ValueHint::Other))
+        })
+}
+
 // ─── Pre-processing ───────────────────────────────────────────────────────────
 
 /// Inspect the subcommands to know if argv[1] is a subcommand keyword.
 fn is_subcommand(s: &str) -> bool {
-    matches!(s, "search" | "add" | "remove" | "list" | "clear" | "help")
+    matches!(
+        s,
+        "search" | "add" | "remove" | "edit" | "enable" | "disable" | "list" | "clear" | "config"
+            | "completions" | "help"
+    )
 }
 
 /// Extract DEPTH and PATH from argv before handing to clap.
@@ -228,30 +328,33 @@ fn preprocess_argv() -> (Option<usize>, Option<PathBuf>, Vec<OsString>) {
 
 // ─── Ignore flag processing ───────────────────────────────────────────────────
 
-/// Fold multiple -n values into (skip_defaults, skip_config, skip_specific_patterns).
-fn parse_no_ignore(values: &[String]) -> (bool, bool, Vec<String>) {
+/// Fold multiple -n values into (skip_defaults, skip_config, skip_gitignore, skip_specific_patterns).
+///
+/// `-n all` disables everything, including `.gitignore`/`.ignore` loading;
+/// `-n gitignore` disables just that subsystem while leaving the built-in
+/// defaults and config patterns in effect.
+fn parse_no_ignore(values: &[String]) -> (bool, bool, bool, Vec<String>) {
     let mut skip_defaults = false;
     let mut skip_config = false;
+    let mut skip_gitignore = false;
     let mut specifics: Vec<String> = Vec::new();
 
     for v in values {
         match v.as_str() {
-            "all"      => { skip_defaults = true; skip_config = true; }
-            "defaults" => { skip_defaults = true; }
-            "config"   => { skip_config = true; }
-            pattern    => { specifics.push(pattern.to_string()); }
+            "all"       => { skip_defaults = true; skip_config = true; skip_gitignore = true; }
+            "defaults"  => { skip_defaults = true; }
+            "config"    => { skip_config = true; }
+            "gitignore" => { skip_gitignore = true; }
+            pattern     => { specifics.push(pattern.to_string()); }
         }
     }
-    (skip_defaults, skip_config, specifics)
+    (skip_defaults, skip_config, skip_gitignore, specifics)
 }
 
-fn build_ignores_from_patterns(patterns: Vec<String>) -> Vec<Regex> {
+fn build_ignores_from_patterns(patterns: Vec<String>) -> Vec<GlobPattern> {
     patterns
         .iter()
-        .filter_map(|p| {
-            let p = p.trim().replace("*", ".*");
-            Regex::new(&format!("^{}$", p)).ok()
-        })
+        .filter_map(|p| GlobPattern::build(p.trim()).ok())
         .collect()
 }
 
@@ -270,28 +373,69 @@ fn main() {
     // Pre-process: pull out DEPTH and PATH before clap sees argv
     let (raw_depth, raw_path, cleaned_argv) = preprocess_argv();
 
-    // Parse only flags
-    let flags = Flags::parse_from(cleaned_argv);
+    // Parse only flags — via the shared command so completions stay in sync
+    let matches = build_cli().get_matches_from(cleaned_argv);
+    let flags = Flags::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
     // ── Subcommands ───────────────────────────────────────────────────────────
     if let Some(command) = flags.command {
         match command {
             Commands::Add { pattern } => { add_config_pattern(pattern); return; }
             Commands::Remove { pattern } => { remove_config_pattern(pattern); return; }
+            Commands::Edit { old, new } => { edit_config_pattern(old, new); return; }
+            Commands::Disable { pattern } => { disable_config_pattern(pattern); return; }
+            Commands::Enable { pattern } => { enable_config_pattern(pattern); return; }
             Commands::List => { list_config_patterns(); return; }
             Commands::Clear => { clear_config_patterns(); return; }
+            Commands::Config { command } => {
+                match command {
+                    ConfigCommand::Init => init_config(),
+                    ConfigCommand::Test { path, profile } => test_config_patterns(&path, profile.as_deref()),
+                }
+                return;
+            }
 
-            Commands::Search { pattern, path, depth, flat, ignore_patterns } => {
+            Commands::Completions { shell } => {
+                clap_complete::generate(shell, &mut build_cli(), "struct", &mut std::io::stdout());
+                return;
+            }
+
+            Commands::Search { pattern, path, depth, flat, ignore_patterns, size, profile } => {
                 let max_depth = if depth == 0 { usize::MAX } else { depth };
-                let mut all_patterns = load_config_patterns();
+                let mut inline_patterns: Vec<String> = Vec::new();
                 if let Some(inline) = ignore_patterns {
                     for p in inline.split(',') {
                         let p = p.trim().to_string();
-                        if !p.is_empty() { all_patterns.push(p); }
+                        if !p.is_empty() { inline_patterns.push(p); }
+                    }
+                }
+                let custom_ignores = build_ignores_from_patterns(inline_patterns);
+                let (_, skip_config, skip_gitignore, _) = parse_no_ignore(&flags.no_ignore);
+                let config_patterns = if skip_config {
+                    PatternSet::compile(Vec::<String>::new())
+                } else {
+                    compiled_patterns(&path, profile.as_deref())
+                };
+                let ignore_stack = if skip_gitignore {
+                    IgnoreStack::empty()
+                } else {
+                    IgnoreStack::load(&path)
+                };
+
+                let mut size_constraints = Vec::new();
+                for raw in &size {
+                    match SizeConstraint::parse(raw) {
+                        Ok(c) => size_constraints.push(c),
+                        Err(e) => { eprintln!("error: {}", e); return; }
                     }
                 }
-                let custom_ignores = build_ignores_from_patterns(all_patterns);
-                search_files(&pattern, &path, max_depth, flat, &custom_ignores);
+
+                let ignores = search::IgnoreSources {
+                    custom: &custom_ignores,
+                    config: &config_patterns,
+                    gitignore: &ignore_stack,
+                };
+                search_files(&pattern, &path, max_depth, flat, &ignores, &size_constraints);
                 return;
             }
         }
@@ -344,7 +488,7 @@ fn main() {
     };
 
     // ── Ignore config ─────────────────────────────────────────────────────────
-    let (skip_defaults, skip_config, skip_specifics) = parse_no_ignore(&flags.no_ignore);
+    let (skip_defaults, skip_config, skip_gitignore, skip_specifics) = parse_no_ignore(&flags.no_ignore);
 
     // depth 0 + git flags: git filtering is ignored for summary (summary shows dir stats, not file lists)
     if raw_depth == Some(0) {
@@ -352,8 +496,22 @@ fn main() {
         return;
     }
 
-    let config_patterns = if skip_config { Vec::new() } else { load_config_patterns() };
+    // NOTE: the tree renderer still takes flat, OR-matched ignore patterns
+    // (see `custom_ignores` below); giving it the same order-sensitive,
+    // directory-scoped `IgnoreStack` support `search` now has is tracked
+    // alongside display.rs's other ignore plumbing. Until then,
+    // `gitignore::flat_patterns` bridges `.gitignore`/`.ignore` into this
+    // flat list so the plain tree view at least honors them, at the cost of
+    // negation and per-directory precedence.
+    let config_patterns: Vec<String> = if skip_config {
+        Vec::new()
+    } else {
+        resolve_patterns(&start_path, flags.profile.as_deref()).into_iter().map(|(_, pattern)| pattern).collect()
+    };
     let mut all_patterns = config_patterns;
+    if !skip_gitignore {
+        all_patterns.extend(gitignore::flat_patterns(&start_path));
+    }
 
     // Add skip_specifics as additional ignore patterns (un-ignore means remove from
     // default list, handled in display.rs via skip_specific — we pass the first one
@@ -396,4 +554,29 @@ fn main() {
 
     println!("{}", start_path.display().to_string().cyan());
     display_tree(&start_path, &config, 0, "", true);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `search PATTERN PATH` is the most common invocation — regression test
+    /// for `build_cli`'s `mut_arg` calls silently reordering `path` after
+    /// `depth` (fixed by pinning explicit `index`s on the `Search` fields).
+    #[test]
+    fn search_path_and_depth_keep_their_positions() {
+        let matches = build_cli()
+            .try_get_matches_from(["struct", "search", "needle", "/tmp"])
+            .expect("search PATTERN PATH should parse");
+        let flags = Flags::from_arg_matches(&matches).expect("matches should convert to Flags");
+
+        match flags.command {
+            Some(Commands::Search { pattern, path, depth, .. }) => {
+                assert_eq!(pattern, "needle");
+                assert_eq!(path, PathBuf::from("/tmp"));
+                assert_eq!(depth, 0);
+            }
+            other => panic!("expected Commands::Search, got {:?}", other),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+/// One matched filesystem entry collected by a parallel walk.
+pub struct WalkHit {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+struct State {
+    queue: VecDeque<(PathBuf, usize)>,
+    /// Directories that have been enqueued but not yet fully processed.
+    /// Reaches zero exactly when there is no more work left anywhere,
+    /// which is how idle workers know to stop rather than deadlock.
+    pending: usize,
+}
+
+/// Walk `start` across a thread pool the way ripgrep's `ignore` crate does:
+/// each directory is handed to a worker, which reads its entries, classifies
+/// them as ignored/matched, and — for directories that aren't ignored —
+/// enqueues them so any free worker can expand them next. Workers run fully
+/// concurrently; only collecting the final `Vec<WalkHit>` is serialized.
+///
+/// `is_ignored`/`is_match` are invoked from multiple threads at once and
+/// must not depend on unsynchronized shared mutable state.
+pub fn parallel_walk<IsIgnored, IsMatch>(
+    start: &Path,
+    max_depth: usize,
+    is_ignored: IsIgnored,
+    is_match: IsMatch,
+) -> Vec<WalkHit>
+where
+    IsIgnored: Fn(&Path, &str, bool) -> bool + Send + Sync,
+    IsMatch: Fn(&Path, &str, bool) -> bool + Send + Sync,
+{
+    let state = Mutex::new(State {
+        queue: VecDeque::from([(start.to_path_buf(), 0)]),
+        pending: 1,
+    });
+    let cvar = Condvar::new();
+    let results: Mutex<Vec<WalkHit>> = Mutex::new(Vec::new());
+
+    let worker_count = thread::available_parallelism().map(|n| n.get()).unwrap_or(4).min(8);
+
+    thread::scope(|scope| {
+        for _ in 0..worker_count {
+            scope.spawn(|| loop {
+                let next = {
+                    let mut guard = state.lock().unwrap();
+                    loop {
+                        if let Some(item) = guard.queue.pop_front() {
+                            break Some(item);
+                        }
+                        if guard.pending == 0 {
+                            break None;
+                        }
+                        guard = cvar.wait(guard).unwrap();
+                    }
+                };
+                let (dir, depth) = match next {
+                    Some(item) => item,
+                    None => {
+                        cvar.notify_all(); // wake any sibling still waiting
+                        break;
+                    }
+                };
+
+                let mut local_hits = Vec::new();
+                let mut local_dirs = Vec::new();
+
+                let entries = fs::read_dir(&dir).into_iter().flatten().filter_map(|e| e.ok());
+                for entry in entries {
+                    let path = entry.path();
+                    let name = match entry.file_name().into_string() {
+                        Ok(n) => n,
+                        Err(_) => continue,
+                    };
+                    let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+                    let ignored = is_ignored(&path, &name, is_dir);
+
+                    // An ignored directory can still surface as a direct hit
+                    // (so `search "__pycache__"` finds one even though it's
+                    // in the default ignore list) — we just never descend
+                    // into it below. An ignored *file*, though, has no such
+                    // exception: it must never appear in the results at all.
+                    if is_match(&path, &name, is_dir) && (is_dir || !ignored) {
+                        let size = if is_dir {
+                            0
+                        } else {
+                            entry.metadata().map(|m| m.len()).unwrap_or(0)
+                        };
+                        local_hits.push(WalkHit { path: path.clone(), is_dir, size });
+                    }
+
+                    // Entries of a directory at `depth` sit at `depth + 1`;
+                    // only descend while those stay within `max_depth`.
+                    if !ignored && is_dir && depth + 1 < max_depth {
+                        local_dirs.push(path);
+                    }
+                }
+
+                if !local_hits.is_empty() {
+                    results.lock().unwrap().extend(local_hits);
+                }
+
+                {
+                    let mut guard = state.lock().unwrap();
+                    guard.pending += local_dirs.len();
+                    for d in local_dirs {
+                        guard.queue.push_back((d, depth + 1));
+                    }
+                    // This directory is now fully processed; any children it
+                    // queued up keep `pending` from hitting zero early.
+                    guard.pending -= 1;
+                }
+                cvar.notify_all();
+            });
+        }
+    });
+
+    results.into_inner().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glob::GlobPattern;
+    use crate::ignores::matches_custom_pattern;
+
+    /// A file matching the search query but also caught by an ignore source
+    /// (here, a `-i`-style custom pattern) must never surface as a hit —
+    /// regression test for `ignored` only ever gating directory descent and
+    /// never being consulted when collecting file hits.
+    #[test]
+    fn ignored_files_are_excluded_from_hits() {
+        let dir = std::env::temp_dir().join(format!("struct_cli_parallel_walk_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("keep.log"), b"keep").unwrap();
+        fs::write(dir.join("ignoreme.log"), b"ignore").unwrap();
+
+        let custom_ignores = vec![GlobPattern::build("ignoreme.log").unwrap()];
+
+        let hits = parallel_walk(
+            &dir,
+            usize::MAX,
+            |_, name, is_dir| !is_dir && matches_custom_pattern(name, &custom_ignores),
+            |_, name, _| name.ends_with(".log"),
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<String> = hits
+            .iter()
+            .map(|h| h.path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert!(names.contains(&"keep.log".to_string()), "keep.log should still match");
+        assert!(!names.contains(&"ignoreme.log".to_string()), "ignoreme.log should be filtered out");
+    }
+}
@@ -0,0 +1,138 @@
+use regex::Regex;
+
+/// One compiled gitignore-style pattern line.
+pub struct Pattern {
+    negate: bool,
+    dir_only: bool,
+    regex: Regex,
+}
+
+impl Pattern {
+    /// Compile one raw line, or `None` if it's blank/a comment.
+    ///
+    /// `#` comments, `!` negation, a trailing `/` for directory-only, a
+    /// leading/embedded `/` to anchor the pattern (otherwise it matches at
+    /// any depth), `*` confined to a path segment, and `**` spanning them.
+    pub fn compile(raw: &str) -> Option<Self> {
+        let line = raw.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let mut pat = line;
+        let negate = pat.starts_with('!');
+        if negate {
+            pat = &pat[1..];
+        }
+
+        let dir_only = pat.ends_with('/');
+        if dir_only {
+            pat = &pat[..pat.len() - 1];
+        }
+        if pat.is_empty() {
+            return None;
+        }
+
+        let anchored = pat.starts_with('/') || pat[..pat.len().saturating_sub(1)].contains('/');
+        let pat = pat.strip_prefix('/').unwrap_or(pat);
+
+        let regex_body = glob_to_regex(pat);
+        let regex_str = if anchored {
+            format!("^{}$", regex_body)
+        } else {
+            format!("^(.*/)?{}$", regex_body)
+        };
+
+        let regex = Regex::new(&regex_str).ok()?;
+        Some(Pattern { negate, dir_only, regex })
+    }
+
+    pub fn is_match(&self, rel_path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        self.regex.is_match(rel_path)
+    }
+}
+
+/// Translate gitignore glob syntax into a regex body (no anchors added yet).
+/// `**` spans path separators, a lone `*` does not, `?` matches one
+/// non-separator character.
+pub fn glob_to_regex(pat: &str) -> String {
+    let mut out = String::new();
+    let chars: Vec<char> = pat.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' => {
+                if chars.get(i + 1) == Some(&'*') {
+                    out.push_str(".*");
+                    i += 2;
+                    if chars.get(i) == Some(&'/') {
+                        i += 1;
+                    }
+                } else {
+                    out.push_str("[^/]*");
+                    i += 1;
+                }
+            }
+            '?' => {
+                out.push_str("[^/]");
+                i += 1;
+            }
+            c => {
+                out.push_str(&regex::escape(&c.to_string()));
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// An ordered, pre-compiled set of gitignore-style patterns.
+///
+/// Patterns are evaluated top to bottom and the *last* one to match decides
+/// whether a path is ignored, so a later `!foo` can re-include a path an
+/// earlier pattern excluded. Compiling once at load time (rather than
+/// re-parsing per path) is what makes checking this cheap enough to call on
+/// every entry of a walk.
+pub struct PatternSet {
+    patterns: Vec<Pattern>,
+}
+
+impl PatternSet {
+    pub fn compile<I: IntoIterator<Item = S>, S: AsRef<str>>(lines: I) -> Self {
+        let patterns = lines.into_iter().filter_map(|l| Pattern::compile(l.as_ref())).collect();
+        PatternSet { patterns }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Does any pattern in this set have an opinion on `rel_path`? Returns
+    /// `None` when nothing matched, so a caller layering several `PatternSet`s
+    /// (e.g. one per directory) can leave an outer verdict untouched instead
+    /// of treating "no match here" as "not ignored".
+    pub fn evaluate(&self, rel_path: &str, is_dir: bool) -> Option<bool> {
+        let mut result = None;
+        for pattern in &self.patterns {
+            if pattern.is_match(rel_path, is_dir) {
+                result = Some(!pattern.negate);
+            }
+        }
+        result
+    }
+
+    /// Is `rel_path` ignored? `parent_excluded` should be true if an
+    /// ancestor directory of this path was already excluded — a negation
+    /// can only re-include a path whose parent wasn't itself already
+    /// excluded by an earlier pattern, mirroring git's own rule that you
+    /// can't un-ignore a file inside an ignored directory.
+    pub fn is_ignored(&self, rel_path: &str, is_dir: bool, parent_excluded: bool) -> bool {
+        if parent_excluded {
+            return true;
+        }
+        self.evaluate(rel_path, is_dir).unwrap_or(false)
+    }
+}
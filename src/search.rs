@@ -1,49 +1,35 @@
 use colored::*;
-use regex::Regex;
 use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
 
+use crate::gitignore::IgnoreStack;
+use crate::glob::GlobPattern;
 use crate::ignores::{should_ignore_dir, matches_custom_pattern};
+use crate::parallel_walk::{parallel_walk, WalkHit};
+use crate::patternset::PatternSet;
+use crate::size::{self, SizeConstraint};
 use crate::utils::{format_size, is_executable};
 
-// ─── Match mode ───────────────────────────────────────────────────────────────
-
-/// Glob pattern (has * or ?) → compiled to a full ^..$ regex.
-/// Plain text (no wildcards)  → case-insensitive substring match.
-enum MatchMode {
-    Glob(Regex),
-    Substring(String),
-}
-
-impl MatchMode {
-    fn build(pattern: &str) -> Result<Self, String> {
-        // Empty pattern is not useful and matches everything — reject it
-        if pattern.is_empty() {
-            return Err("pattern cannot be empty — use \"*\" to match everything".to_string());
-        }
-
-        let has_wildcards = pattern.contains('*') || pattern.contains('?');
-        if has_wildcards {
-            // Escape all regex metacharacters, then restore our glob chars
-            let escaped = regex::escape(pattern);
-            let regex_pat = escaped.replace(r"\*", ".*").replace(r"\?", ".");
-            let re = Regex::new(&format!("(?i)^{}$", regex_pat))
-                .map_err(|e| e.to_string())?;
-            Ok(MatchMode::Glob(re))
-        } else {
-            // Plain text: case-insensitive substring
-            Ok(MatchMode::Substring(pattern.to_lowercase()))
-        }
+/// Match an entry against `pattern`: glob patterns containing a `/` are
+/// matched against the path relative to `start_path` (so `src/**/*.rs` only
+/// matches under `src/`); everything else, including plain-text patterns
+/// with a `/`, matches against the bare file name.
+fn matches_entry(pattern: &GlobPattern, path: &Path, name: &str, start_path: &Path) -> bool {
+    if pattern.is_path_pattern() {
+        let rel = path.strip_prefix(start_path).unwrap_or(path);
+        pattern.is_match_path(rel)
+    } else {
+        pattern.is_match_name(name)
     }
+}
 
-    fn is_match(&self, filename: &str) -> bool {
-        match self {
-            MatchMode::Glob(re) => re.is_match(filename),
-            MatchMode::Substring(needle) => filename.to_lowercase().contains(needle.as_str()),
-        }
-    }
+/// Every ignore source `search` layers together, bundled so `search_files`
+/// doesn't grow a parameter per source as new ones get added.
+pub struct IgnoreSources<'a> {
+    pub custom: &'a [GlobPattern],
+    pub config: &'a PatternSet,
+    pub gitignore: &'a IgnoreStack,
 }
 
 // ─── Public API ───────────────────────────────────────────────────────────────
@@ -53,9 +39,10 @@ pub fn search_files(
     start_path: &Path,
     max_depth: usize,
     flat: bool,
-    custom_ignores: &[Regex],
+    ignores: &IgnoreSources,
+    size_constraints: &[SizeConstraint],
 ) {
-    let matcher = match MatchMode::build(pattern) {
+    let matcher = match GlobPattern::build(pattern) {
         Ok(m) => m,
         Err(e) => {
             eprintln!("error: {}", e);
@@ -63,72 +50,59 @@ pub fn search_files(
         }
     };
 
-    let mut found_count = 0;
-    let mut matching_paths: HashSet<PathBuf> = HashSet::new();
-    let mut flat_results: Vec<(PathBuf, bool, u64)> = Vec::new(); // (path, is_dir, size)
-
-    for entry in WalkDir::new(start_path)
-        .follow_links(false)
-        .max_depth(max_depth)
-        .into_iter()
-        .filter_entry(|e| {
-            // Always allow the root itself
-            if e.depth() == 0 {
+    // Fan out the walk across a thread pool: each worker classifies a
+    // directory's entries (ignored? a match?) independently, so matching and
+    // size lookups all happen off the calling thread. We only serialize
+    // afterwards, to assemble the ancestor set and print deterministically.
+    let hits: Vec<WalkHit> = parallel_walk(
+        start_path,
+        max_depth,
+        |path, name, is_dir| {
+            if is_dir && (should_ignore_dir(name) || matches_custom_pattern(name, ignores.custom)) {
                 return true;
             }
-            let name = match e.file_name().to_str() {
-                Some(n) => n,
-                None => return true,
-            };
-            // For directories: prune ignored ones UNLESS the dir itself is a match.
-            // This lets `search "__pycache__"` find those dirs even though they're
-            // in the default ignore list. We won't descend inside them (filter_entry
-            // prunes recursion) so we just surface them as direct hits.
-            if e.file_type().is_dir() {
-                let is_ignored = should_ignore_dir(name)
-                    || matches_custom_pattern(name, custom_ignores);
-                if is_ignored {
-                    return matcher.is_match(name);
-                }
+            if ignores.gitignore.is_ignored(path, is_dir) {
+                return true;
             }
-            true
-        })
-        .filter_map(|e| e.ok())
-    {
-        if entry.depth() == 0 {
-            continue; // skip root
-        }
+            // Skip the relative-path allocation below when there are no
+            // config patterns to check it against — the common case for a
+            // user with no `.structignore`/profile/global ignores set up.
+            if ignores.config.is_empty() {
+                return false;
+            }
+            let rel = path.strip_prefix(start_path).unwrap_or(path).to_string_lossy().replace('\\', "/");
+            ignores.config.is_ignored(&rel, is_dir, false)
+        },
+        |path, name, is_dir| {
+            if !matches_entry(&matcher, path, name, start_path) {
+                return false;
+            }
+            // Size constraints only ever apply to files, never directories.
+            if is_dir || size_constraints.is_empty() {
+                return true;
+            }
+            let file_size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            size::matches_all(size_constraints, file_size)
+        },
+    );
 
-        let filename = match entry.file_name().to_str() {
-            Some(n) => n,
-            None => continue,
-        };
-
-        if matcher.is_match(filename) {
-            let file_path = entry.path().to_path_buf();
-            let is_dir = entry.file_type().is_dir();
-
-            if flat {
-                let size = if is_dir {
-                    0
-                } else {
-                    entry.metadata().map(|m| m.len()).unwrap_or(0)
-                };
-                flat_results.push((file_path, is_dir, size));
-            } else {
-                matching_paths.insert(file_path.clone());
-                // Record all ancestor dirs so the tree renders correctly
-                let mut cur = file_path.parent();
-                while let Some(parent) = cur {
-                    if parent == start_path {
-                        break;
-                    }
-                    matching_paths.insert(parent.to_path_buf());
-                    cur = parent.parent();
+    let found_count = hits.len();
+    let mut matching_paths: HashSet<PathBuf> = HashSet::new();
+    let mut flat_results: Vec<(PathBuf, bool, u64)> = Vec::new(); // (path, is_dir, size)
+
+    for hit in hits {
+        if flat {
+            flat_results.push((hit.path, hit.is_dir, hit.size));
+        } else {
+            let mut cur = hit.path.parent().map(|p| p.to_path_buf());
+            matching_paths.insert(hit.path);
+            while let Some(parent) = cur {
+                if parent == start_path {
+                    break;
                 }
+                cur = parent.parent().map(|p| p.to_path_buf());
+                matching_paths.insert(parent);
             }
-
-            found_count += 1;
         }
     }
 
@@ -148,7 +122,10 @@ pub fn search_files(
     println!();
 
     if flat {
-        flat_results.sort_by(|a, b| a.0.cmp(&b.0));
+        flat_results.sort_by_key(|(path, is_dir, _)| {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+            (!is_dir, name)
+        });
         for (path, is_dir, size) in flat_results {
             if is_dir {
                 println!("{}", format!("{}/", path.display()).blue().bold());
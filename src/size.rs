@@ -0,0 +1,77 @@
+/// How a parsed `--size` constraint compares against a file's actual size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SizeOp {
+    AtLeast,
+    AtMost,
+    Exact,
+}
+
+/// A single fd-style size constraint, e.g. `+10M`, `-500k`, `1G`.
+#[derive(Debug, Clone, Copy)]
+pub struct SizeConstraint {
+    op: SizeOp,
+    bytes: u64,
+}
+
+impl SizeConstraint {
+    /// Parse one constraint: an optional leading `+` (at least) or `-` (at
+    /// most, bare means exact), a number, and a unit suffix — `k`/`M`/`G`/`T`
+    /// for decimal (1000-based) units, or `ki`/`Mi`/`Gi`/`Ti` for binary
+    /// (1024-based) ones. A trailing `b`/`B` on the suffix is ignored so
+    /// `10MB` and `10M` parse the same way. No suffix means bytes.
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return Err("size constraint cannot be empty".to_string());
+        }
+
+        let (op, rest) = match raw.as_bytes()[0] {
+            b'+' => (SizeOp::AtLeast, &raw[1..]),
+            b'-' => (SizeOp::AtMost, &raw[1..]),
+            _ => (SizeOp::Exact, raw),
+        };
+
+        let split_at = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let (num_str, suffix) = rest.split_at(split_at);
+
+        let num: f64 = num_str
+            .parse()
+            .map_err(|_| format!("invalid size '{}'", raw))?;
+        let multiplier = unit_multiplier(suffix)
+            .ok_or_else(|| format!("unknown size suffix '{}' in '{}'", suffix, raw))?;
+
+        Ok(SizeConstraint { op, bytes: (num * multiplier).round() as u64 })
+    }
+
+    pub fn matches(&self, size: u64) -> bool {
+        match self.op {
+            SizeOp::AtLeast => size >= self.bytes,
+            SizeOp::AtMost => size <= self.bytes,
+            SizeOp::Exact => size == self.bytes,
+        }
+    }
+}
+
+/// True if `size` satisfies every constraint (constraints AND together, so
+/// `-S +1M -S -100M` means "between 1M and 100M").
+pub fn matches_all(constraints: &[SizeConstraint], size: u64) -> bool {
+    constraints.iter().all(|c| c.matches(size))
+}
+
+fn unit_multiplier(suffix: &str) -> Option<f64> {
+    let s = suffix.trim_end_matches(['b', 'B']).to_lowercase();
+    Some(match s.as_str() {
+        "" => 1.0,
+        "k" => 1_000.0,
+        "ki" => 1024.0,
+        "m" => 1_000_000.0,
+        "mi" => 1024.0 * 1024.0,
+        "g" => 1_000_000_000.0,
+        "gi" => 1024f64.powi(3),
+        "t" => 1_000_000_000_000.0,
+        "ti" => 1024f64.powi(4),
+        _ => return None,
+    })
+}